@@ -0,0 +1,255 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use axum::RequestPartsExt;
+use axum_extra::extract::CookieJar;
+use axum_extra::headers::authorization::Bearer;
+use axum_extra::headers::Authorization;
+use axum_extra::TypedHeader;
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::http::{ApiContext, Error, Result};
+
+/// The name of the cookie used as a fallback for the `Authorization` header.
+const ACCESS_TOKEN_COOKIE: &str = "access_token";
+
+/// Discriminates an access token from a refresh token in the signed payload, so one can never
+/// be decoded as if it were the other even though `sub`/`exp` alone would parse either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum TokenType {
+    Access,
+    Refresh,
+}
+
+/// The claims encoded in an access token.
+///
+/// Extracted directly from a request by taking `claims: Claims` as a handler parameter - see
+/// the [`FromRequestParts`] impl below.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Claims {
+    /// The id of the authenticated user.
+    pub sub: i32,
+
+    /// Expiration time, as a UTC timestamp. Required by the `jsonwebtoken` crate.
+    pub exp: i64,
+
+    #[serde(rename = "typ")]
+    token_type: TokenType,
+}
+
+/// The claims encoded in a refresh token.
+///
+/// Kept as a distinct type from [`Claims`] so that a refresh token can never be accepted in
+/// place of an access token, or vice versa - both the shape (enforced by the compiler) and the
+/// `typ` claim (enforced by `decode`) have to match.
+#[derive(Debug, Serialize, Deserialize)]
+struct RefreshClaims {
+    sub: i32,
+    exp: i64,
+    #[serde(rename = "typ")]
+    token_type: TokenType,
+}
+
+/// The response body returned for a successful login, refresh, etc.
+#[derive(Debug, Serialize)]
+pub struct Token {
+    pub access_token: String,
+    pub token_type: &'static str,
+}
+
+/// The response body returned alongside a [`Token`] when issuing a new refresh token.
+#[derive(Debug, Serialize)]
+pub struct RefreshToken {
+    pub refresh_token: String,
+    pub token_type: &'static str,
+}
+
+impl Claims {
+    /// Build the claims for a new access token belonging to `user_id`, expiring
+    /// `config.jwt_max_age` seconds from now.
+    pub fn new(user_id: i32, config: &Config) -> Self {
+        Self {
+            sub: user_id,
+            exp: expires_in(config.jwt_max_age),
+            token_type: TokenType::Access,
+        }
+    }
+
+    /// Sign these claims into a bearer [`Token`].
+    pub fn encode(&self, config: &Config) -> Result<Token> {
+        let access_token = encode(
+            &Header::default(),
+            self,
+            &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+        )
+        .context("failed to encode access token")?;
+
+        Ok(Token {
+            access_token,
+            token_type: "Bearer",
+        })
+    }
+
+    fn decode(token: &str, config: &Config) -> Result<Self> {
+        let claims = decode::<Self>(
+            token,
+            &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+            &Validation::default(),
+        )
+        .map(|data| data.claims)
+        .map_err(|_| Error::Unauthorized)?;
+
+        if claims.token_type != TokenType::Access {
+            return Err(Error::Unauthorized);
+        }
+
+        Ok(claims)
+    }
+}
+
+impl RefreshClaims {
+    /// Build the claims for a new refresh token belonging to `user_id`, expiring
+    /// `config.jwt_refresh_max_age` seconds from now.
+    fn new(user_id: i32, config: &Config) -> Self {
+        Self {
+            sub: user_id,
+            exp: expires_in(config.jwt_refresh_max_age),
+            token_type: TokenType::Refresh,
+        }
+    }
+
+    fn encode(&self, config: &Config) -> Result<RefreshToken> {
+        let refresh_token = encode(
+            &Header::default(),
+            self,
+            &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+        )
+        .context("failed to encode refresh token")?;
+
+        Ok(RefreshToken {
+            refresh_token,
+            token_type: "Bearer",
+        })
+    }
+
+    fn decode(token: &str, config: &Config) -> Result<Self> {
+        let claims = decode::<Self>(
+            token,
+            &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+            &Validation::default(),
+        )
+        .map(|data| data.claims)
+        .map_err(|_| Error::Unauthorized)?;
+
+        if claims.token_type != TokenType::Refresh {
+            return Err(Error::Unauthorized);
+        }
+
+        Ok(claims)
+    }
+}
+
+/// Issue a fresh access/refresh token pair for `user_id`, e.g. on login.
+pub fn issue_tokens(user_id: i32, config: &Config) -> Result<(Token, RefreshToken)> {
+    let access_token = Claims::new(user_id, config).encode(config)?;
+    let refresh_token = RefreshClaims::new(user_id, config).encode(config)?;
+
+    Ok((access_token, refresh_token))
+}
+
+/// Validate a refresh token and mint a new access [`Token`] for its subject.
+pub fn refresh_access_token(refresh_token: &str, config: &Config) -> Result<Token> {
+    let claims = RefreshClaims::decode(refresh_token, config)?;
+
+    Claims::new(claims.sub, config).encode(config)
+}
+
+fn expires_in(seconds: i64) -> i64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the unix epoch")
+        .as_secs() as i64;
+
+    now + seconds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config() -> Config {
+        Config {
+            database_url: "postgres://localhost/test".to_string(),
+            port: 8080,
+            address: "127.0.0.1".to_string(),
+            jwt_secret: "test-secret".to_string(),
+            jwt_max_age: 900,
+            jwt_refresh_max_age: 1_209_600,
+            cors_allowed_origins: Vec::new(),
+            request_body_limit_bytes: 2 * 1024 * 1024,
+            compression_enabled: false,
+            upload_dir: "./uploads".into(),
+            upload_max_file_size_bytes: 5 * 1024 * 1024,
+            static_dir: "../web/out".into(),
+        }
+    }
+
+    #[test]
+    fn access_token_roundtrips() {
+        let config = test_config();
+        let token = Claims::new(42, &config).encode(&config).unwrap();
+
+        let claims = Claims::decode(&token.access_token, &config).unwrap();
+        assert_eq!(claims.sub, 42);
+    }
+
+    #[test]
+    fn refresh_token_roundtrips() {
+        let config = test_config();
+        let token = RefreshClaims::new(7, &config).encode(&config).unwrap();
+
+        let claims = RefreshClaims::decode(&token.refresh_token, &config).unwrap();
+        assert_eq!(claims.sub, 7);
+    }
+
+    #[test]
+    fn refresh_token_is_rejected_as_an_access_token() {
+        let config = test_config();
+        let token = RefreshClaims::new(1, &config).encode(&config).unwrap();
+
+        assert!(Claims::decode(&token.refresh_token, &config).is_err());
+    }
+
+    #[test]
+    fn access_token_is_rejected_as_a_refresh_token() {
+        let config = test_config();
+        let token = Claims::new(1, &config).encode(&config).unwrap();
+
+        assert!(RefreshClaims::decode(&token.access_token, &config).is_err());
+    }
+}
+
+#[axum::async_trait]
+impl FromRequestParts<ApiContext> for Claims {
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, ctx: &ApiContext) -> Result<Self> {
+        let token = match parts
+            .extract::<TypedHeader<Authorization<Bearer>>>()
+            .await
+        {
+            Ok(TypedHeader(Authorization(bearer))) => bearer.token().to_owned(),
+            Err(_) => CookieJar::from_headers(&parts.headers)
+                .get(ACCESS_TOKEN_COOKIE)
+                .map(|cookie| cookie.value().to_owned())
+                .ok_or(Error::Unauthorized)?,
+        };
+
+        Self::decode(&token, &ctx.config)
+    }
+}
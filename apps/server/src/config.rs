@@ -1,21 +1,354 @@
-/// The configuration parameters for the application
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::Parser;
+use serde::Deserialize;
+
+const DEFAULT_JWT_MAX_AGE: i64 = 900;
+const DEFAULT_JWT_REFRESH_MAX_AGE: i64 = 1_209_600;
+const DEFAULT_REQUEST_BODY_LIMIT_BYTES: usize = 2 * 1024 * 1024;
+const DEFAULT_COMPRESSION_ENABLED: bool = true;
+const DEFAULT_UPLOAD_DIR: &str = "./uploads";
+const DEFAULT_UPLOAD_MAX_FILE_SIZE_BYTES: usize = 5 * 1024 * 1024;
+const DEFAULT_STATIC_DIR: &str = "../web/out";
+
+/// The configuration parameters for the application.
 ///
-/// These can either loaded from command-line, or pulled from environment variables.
+/// Values are resolved from multiple sources, in order of precedence (highest wins):
 ///
-/// Environment variables are preferred.
+/// 1. Command-line arguments
+/// 2. Environment variables
+/// 3. A `config.toml` file, given with `--config`/`CONFIG`
+/// 4. Built-in defaults
 ///
-/// For development convenience, these can also be read from a `.env` file in the working
-/// directory where the application is started.
+/// For development convenience, environment variables can also be read from a `.env` file in
+/// the working directory where the application is started.
 ///
-/// See `.env.example` in the repository root for details
-#[derive(clap::Parser)]
+/// See `.env.example` in the repository root and `apps/server/config.example.toml` for details.
+#[derive(Debug, Clone)]
 pub struct Config {
-    #[clap(long, env)]
     pub database_url: String,
+    pub port: u64,
+    pub address: String,
+    pub jwt_secret: String,
+    pub jwt_max_age: i64,
+    pub jwt_refresh_max_age: i64,
+
+    /// Origins allowed to make cross-origin requests, parsed from a comma-separated list.
+    pub cors_allowed_origins: Vec<String>,
+
+    /// Maximum accepted request body size, in bytes.
+    pub request_body_limit_bytes: usize,
+
+    /// Whether responses should be gzip/brotli-compressed. Disable for easier local debugging.
+    pub compression_enabled: bool,
+
+    /// Directory where uploaded files (and their derived variants) are stored.
+    pub upload_dir: PathBuf,
+
+    /// Maximum accepted size of a single uploaded file, in bytes.
+    pub upload_max_file_size_bytes: usize,
 
+    /// Directory the compiled frontend is read from when assets aren't embedded in the binary
+    /// (i.e. without the `embed-assets` feature).
+    pub static_dir: PathBuf,
+}
+
+/// Raw command-line/environment arguments.
+///
+/// Every field is optional here - a value left unset may still be filled in from the config
+/// file (given by `config`) or a default. [`Config::load`] is what enforces which fields are actually
+/// required.
+#[derive(clap::Parser, Default)]
+struct Args {
+    /// Path to a TOML file providing defaults for any value not set via CLI/env.
     #[clap(long, env)]
-    pub port: u64,
+    config: Option<PathBuf>,
 
     #[clap(long, env)]
-    pub address: String,
+    database_url: Option<String>,
+
+    #[clap(long, env)]
+    port: Option<u64>,
+
+    #[clap(long, env)]
+    address: Option<String>,
+
+    #[clap(long, env)]
+    jwt_secret: Option<String>,
+
+    #[clap(long, env)]
+    jwt_max_age: Option<i64>,
+
+    #[clap(long, env)]
+    jwt_refresh_max_age: Option<i64>,
+
+    /// Comma-separated list of origins allowed to make cross-origin requests.
+    #[clap(long, env)]
+    cors_allowed_origins: Option<String>,
+
+    #[clap(long, env)]
+    request_body_limit_bytes: Option<usize>,
+
+    #[clap(long, env)]
+    compression_enabled: Option<bool>,
+
+    #[clap(long, env)]
+    upload_dir: Option<PathBuf>,
+
+    #[clap(long, env)]
+    upload_max_file_size_bytes: Option<usize>,
+
+    #[clap(long, env)]
+    static_dir: Option<PathBuf>,
+}
+
+/// The shape of `config.toml`. Settings are grouped into sections so the file stays readable
+/// as more of them are added.
+#[derive(Debug, Default, Deserialize)]
+struct FileConfig {
+    #[serde(default)]
+    server: ServerSection,
+    #[serde(default)]
+    database: DatabaseSection,
+    #[serde(default)]
+    jwt: JwtSection,
+    #[serde(default)]
+    cors: CorsSection,
+    #[serde(default)]
+    uploads: UploadsSection,
+    #[serde(default)]
+    frontend: FrontendSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ServerSection {
+    address: Option<String>,
+    port: Option<u64>,
+    request_body_limit_bytes: Option<usize>,
+    compression_enabled: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DatabaseSection {
+    url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct JwtSection {
+    secret: Option<String>,
+    max_age: Option<i64>,
+    refresh_max_age: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct CorsSection {
+    allowed_origins: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct UploadsSection {
+    dir: Option<PathBuf>,
+    max_file_size_bytes: Option<usize>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct FrontendSection {
+    static_dir: Option<PathBuf>,
+}
+
+/// Split a comma-separated list of origins into a trimmed, non-empty [`Vec<String>`].
+fn parse_origins(origins: Option<String>) -> Vec<String> {
+    origins
+        .map(|origins| {
+            origins
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl Config {
+    /// Parse CLI arguments and environment variables, merge in `config.toml` (if given via
+    /// `--config`), and apply defaults for anything still unset.
+    pub fn load() -> anyhow::Result<Self> {
+        let args = Args::parse();
+
+        let file = match &args.config {
+            Some(path) => {
+                let contents = fs::read_to_string(path).with_context(|| {
+                    format!("failed to read config file at {}", path.display())
+                })?;
+
+                toml::from_str(&contents).with_context(|| {
+                    format!("failed to parse config file at {}", path.display())
+                })?
+            }
+            None => FileConfig::default(),
+        };
+
+        Self::merge(args, file)
+    }
+
+    /// Apply the CLI/env > file > default precedence to produce the final [`Config`].
+    ///
+    /// Split out from [`Config::load`] so the merge logic can be unit tested against plain
+    /// `Args`/`FileConfig` values, without going through `clap`'s process-argv parsing.
+    fn merge(args: Args, file: FileConfig) -> anyhow::Result<Self> {
+        let request_body_limit_bytes = args
+            .request_body_limit_bytes
+            .or(file.server.request_body_limit_bytes)
+            .unwrap_or(DEFAULT_REQUEST_BODY_LIMIT_BYTES);
+
+        // A single upload can never exceed the whole request, so clamp it to
+        // `request_body_limit_bytes` - otherwise an upload under this limit but over the
+        // global one would be rejected by `RequestBodyLimitLayer` before ever reaching the
+        // handler's own check, making the configured value silently unreachable.
+        let upload_max_file_size_bytes = args
+            .upload_max_file_size_bytes
+            .or(file.uploads.max_file_size_bytes)
+            .unwrap_or(DEFAULT_UPLOAD_MAX_FILE_SIZE_BYTES)
+            .min(request_body_limit_bytes);
+
+        Ok(Self {
+            database_url: args.database_url.or(file.database.url).context(
+                "`database_url` must be set via --database-url, DATABASE_URL, or the config file",
+            )?,
+            port: args
+                .port
+                .or(file.server.port)
+                .context("`port` must be set via --port, PORT, or the config file")?,
+            address: args.address.or(file.server.address).context(
+                "`address` must be set via --address, ADDRESS, or the config file",
+            )?,
+            jwt_secret: args.jwt_secret.or(file.jwt.secret).context(
+                "`jwt_secret` must be set via --jwt-secret, JWT_SECRET, or the config file",
+            )?,
+            jwt_max_age: args
+                .jwt_max_age
+                .or(file.jwt.max_age)
+                .unwrap_or(DEFAULT_JWT_MAX_AGE),
+            jwt_refresh_max_age: args
+                .jwt_refresh_max_age
+                .or(file.jwt.refresh_max_age)
+                .unwrap_or(DEFAULT_JWT_REFRESH_MAX_AGE),
+            cors_allowed_origins: parse_origins(
+                args.cors_allowed_origins.or(file.cors.allowed_origins),
+            ),
+            request_body_limit_bytes,
+            compression_enabled: args
+                .compression_enabled
+                .or(file.server.compression_enabled)
+                .unwrap_or(DEFAULT_COMPRESSION_ENABLED),
+            upload_dir: args
+                .upload_dir
+                .or(file.uploads.dir)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_UPLOAD_DIR)),
+            upload_max_file_size_bytes,
+            static_dir: args
+                .static_dir
+                .or(file.frontend.static_dir)
+                .unwrap_or_else(|| PathBuf::from(DEFAULT_STATIC_DIR)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn required_args() -> Args {
+        Args {
+            database_url: Some("postgres://localhost/test".to_string()),
+            port: Some(8080),
+            address: Some("127.0.0.1".to_string()),
+            jwt_secret: Some("test-secret".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_origins_splits_trims_and_drops_empties() {
+        let origins = parse_origins(Some(" http://a.test ,http://b.test,,".to_string()));
+
+        assert_eq!(origins, vec!["http://a.test", "http://b.test"]);
+    }
+
+    #[test]
+    fn parse_origins_of_none_is_empty() {
+        assert!(parse_origins(None).is_empty());
+    }
+
+    #[test]
+    fn cli_args_take_precedence_over_the_config_file() {
+        let args = Args {
+            port: Some(9000),
+            ..required_args()
+        };
+        let file = FileConfig {
+            server: ServerSection {
+                port: Some(1234),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let config = Config::merge(args, file).unwrap();
+
+        assert_eq!(config.port, 9000);
+    }
+
+    #[test]
+    fn config_file_fills_in_values_missing_from_args() {
+        let file = FileConfig {
+            server: ServerSection {
+                port: Some(1234),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let config = Config::merge(required_args(), file).unwrap();
+
+        assert_eq!(config.port, 1234);
+    }
+
+    #[test]
+    fn defaults_apply_when_unset_by_args_or_file() {
+        let config = Config::merge(required_args(), FileConfig::default()).unwrap();
+
+        assert_eq!(config.jwt_max_age, DEFAULT_JWT_MAX_AGE);
+        assert_eq!(
+            config.request_body_limit_bytes,
+            DEFAULT_REQUEST_BODY_LIMIT_BYTES
+        );
+        assert_eq!(config.upload_dir, PathBuf::from(DEFAULT_UPLOAD_DIR));
+    }
+
+    #[test]
+    fn missing_required_value_is_an_error() {
+        let args = Args {
+            database_url: None,
+            ..required_args()
+        };
+
+        assert!(Config::merge(args, FileConfig::default()).is_err());
+    }
+
+    #[test]
+    fn upload_max_file_size_is_clamped_to_the_request_body_limit() {
+        let args = Args {
+            request_body_limit_bytes: Some(1024),
+            upload_max_file_size_bytes: Some(4096),
+            ..required_args()
+        };
+
+        let config = Config::merge(args, FileConfig::default()).unwrap();
+
+        assert_eq!(config.upload_max_file_size_bytes, 1024);
+    }
 }
@@ -1,13 +1,19 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 
-use axum::http::{header::WWW_AUTHENTICATE, HeaderMap, HeaderValue, StatusCode};
+use axum::http::{
+    header::{CONTENT_TYPE, LOCATION, WWW_AUTHENTICATE},
+    HeaderMap, HeaderValue, StatusCode, Uri,
+};
 use axum::response::IntoResponse;
 use axum::Json;
 use sqlx::error::DatabaseError;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The media type mandated by RFC 7807 for a problem details body.
+pub(crate) const PROBLEM_JSON: &str = "application/problem+json";
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     /// Return `404 Not Found`
@@ -33,6 +39,15 @@ pub enum Error {
     #[error("authorization required")]
     Unauthorized,
 
+    /// Return `409 Conflict`, e.g. when a unique constraint violation is surfaced through
+    /// [`ResultExt::on_constraint`].
+    #[error("the request conflicts with the current state of the resource")]
+    Conflict,
+
+    /// Redirect the client to `.0` instead of returning a problem details body.
+    #[error("redirecting to {0}")]
+    Redirect(Uri),
+
     /// Automatically return `500 Internal Server Error` on a `sqlx::Error`
     ///
     /// Via the generated `From<sqlx::Error> for Error` impl,
@@ -77,6 +92,8 @@ impl Error {
             Self::Forbidden => StatusCode::FORBIDDEN,
             Self::Unauthorized => StatusCode::UNAUTHORIZED,
             Self::BadRequest { .. } => StatusCode::BAD_REQUEST,
+            Self::Conflict => StatusCode::CONFLICT,
+            Self::Redirect(_) => StatusCode::FOUND,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -88,40 +105,83 @@ impl Error {
             Self::UnprocessableEntity => "Unprocessable Entity",
             Self::Forbidden => "Forbidden",
             Self::NotFound => "Not Found",
+            Self::Conflict => "Conflict",
+            Self::Redirect(_) => "Found",
             _ => "Internal Server Error",
         }
         .to_string()
     }
 }
 
-#[derive(Debug, serde::Serialize)]
+/// A [RFC 7807](https://www.rfc-editor.org/rfc/rfc7807) problem details body.
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
 pub struct ErrorBody {
+    /// A URI reference identifying the problem type. `about:blank` when the status code alone
+    /// is descriptive enough, per the RFC's recommendation.
+    #[serde(rename = "type")]
+    type_: String,
+
     title: String,
     status: u16,
-    message: String,
+    detail: String,
+
+    /// The request path this error was produced for, filled in by the `problem_details`
+    /// middleware once the request/response round-trip has completed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instance: Option<String>,
+
+    /// The `RequestId` generated by `RequestIdLayer`, so logs and client-reported errors can be
+    /// cross-referenced. Also filled in by the `problem_details` middleware.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    request_id: Option<String>,
+
+    /// Per-field validation errors, present only for [`Error::BadRequest`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<HashMap<Cow<'static, str>, Vec<Cow<'static, str>>>>,
 }
 
 impl IntoResponse for Error {
     fn into_response(self) -> axum::response::Response {
-        let e = ErrorBody::from(self);
-        let status = StatusCode::from_u16(e.status).unwrap();
-        let header: HeaderMap = match status {
-            StatusCode::UNAUTHORIZED => [(WWW_AUTHENTICATE, HeaderValue::from_static("Token"))]
-                .into_iter()
-                .collect(),
-            _ => HeaderMap::new(),
-        };
+        let status = self.status_code();
+
+        if let Self::Redirect(uri) = &self {
+            let location = HeaderValue::from_str(&uri.to_string())
+                .unwrap_or_else(|_| HeaderValue::from_static("/"));
+
+            return (status, [(LOCATION, location)]).into_response();
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON));
+
+        if status == StatusCode::UNAUTHORIZED {
+            headers.insert(WWW_AUTHENTICATE, HeaderValue::from_static("Token"));
+        }
 
-        (status, header, Json(e)).into_response()
+        (status, headers, Json(ErrorBody::from(self))).into_response()
     }
 }
 
 impl From<Error> for ErrorBody {
     fn from(error: Error) -> Self {
+        let type_ = "about:blank".to_string();
+        let title = error.title();
+        let status = error.status_code().as_u16();
+        let detail = error.to_string();
+
+        let errors = match error {
+            Error::BadRequest { errors } => Some(errors),
+            _ => None,
+        };
+
         Self {
-            title: error.title(),
-            message: error.to_string(),
-            status: error.status_code().as_u16(),
+            type_,
+            title,
+            status,
+            detail,
+            instance: None,
+            request_id: None,
+            errors,
         }
     }
 }
@@ -176,3 +236,53 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_code_mapping() {
+        assert_eq!(Error::NotFound.status_code(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            Error::UnprocessableEntity.status_code(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+        assert_eq!(Error::Forbidden.status_code(), StatusCode::FORBIDDEN);
+        assert_eq!(Error::Unauthorized.status_code(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            Error::bad_request([("field", "bad")]).status_code(),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(Error::Conflict.status_code(), StatusCode::CONFLICT);
+        assert_eq!(
+            Error::Anyhow(anyhow::anyhow!("boom")).status_code(),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+
+    #[test]
+    fn title_mapping() {
+        assert_eq!(Error::NotFound.title(), "Not Found");
+        assert_eq!(Error::Unauthorized.title(), "Unauthorized");
+        assert_eq!(
+            Error::bad_request([("field", "bad")]).title(),
+            "Bad Request"
+        );
+        assert_eq!(
+            Error::Anyhow(anyhow::anyhow!("boom")).title(),
+            "Internal Server Error"
+        );
+    }
+
+    #[test]
+    fn error_body_carries_per_field_errors_only_for_bad_request() {
+        let body = ErrorBody::from(Error::bad_request([("username", "already taken")]));
+
+        assert_eq!(body.status, 400);
+        assert!(body.errors.is_some());
+
+        let body = ErrorBody::from(Error::NotFound);
+        assert!(body.errors.is_none());
+    }
+}
@@ -1,8 +1,10 @@
 use crate::config::Config;
 use anyhow::Context;
 use axum::error_handling::HandleErrorLayer;
-use axum::http::Uri;
-use axum::response::IntoResponse;
+use axum::http::header::{CONTENT_LENGTH, CONTENT_TYPE};
+use axum::http::HeaderValue;
+use axum::middleware::Next;
+use axum::response::Response;
 use axum::BoxError;
 use axum::{body::Body, http::Request, Router};
 use sqlx::PgPool;
@@ -21,11 +23,22 @@ use tower::limit::RateLimitLayer;
 /// Defines a common error type to use for all request handlers
 mod error;
 
+/// Aggregates the OpenAPI spec generated from the annotated handlers in `routes`
+mod openapi;
+
 /// Contains all the routes of the application
 mod routes;
 
+/// Serves the embedded/on-disk frontend build as a fallback for non-API routes
+mod static_files;
+
 pub use error::{Error, Result, ResultExt};
+use error::PROBLEM_JSON;
 
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tower_http::decompression::RequestDecompressionLayer;
+use tower_http::limit::RequestBodyLimitLayer;
 use tower_http::trace::TraceLayer;
 
 #[derive(Clone)]
@@ -44,8 +57,13 @@ pub async fn serve(config: Config, db: PgPool) -> anyhow::Result<()> {
             .unwrap(),
     );
 
+    let cors = cors_layer(&config);
+    let compression_layer = config.compression_enabled.then(CompressionLayer::new);
+    let body_limit_layer = RequestBodyLimitLayer::new(config.request_body_limit_bytes);
+
     let app: Router = Router::<ApiContext>::new()
         .merge(routes::router())
+        .fallback(static_files::fallback)
         .layer(
             ServiceBuilder::new()
                 .layer(RequestIdLayer)
@@ -70,9 +88,19 @@ pub async fn serve(config: Config, db: PgPool) -> anyhow::Result<()> {
                 }))
                 .layer(GovernorLayer {
                     config: Box::leak(governor_conf),
-                }),
+                })
+                .layer(cors)
+                // Must run before `body_limit_layer` below, so the limit is enforced against the
+                // decompressed payload size - otherwise a small compressed body could expand past
+                // the configured limit with nothing downstream to stop it.
+                .layer(RequestDecompressionLayer::new())
+                .layer(body_limit_layer)
+                .option_layer(compression_layer)
+                // Must be innermost relative to `compression_layer` above, so it inspects the
+                // response body *before* it's compressed - otherwise `serde_json::from_slice`
+                // fails on the compressed bytes and `instance`/`request_id` never get injected.
+                .layer(axum::middleware::from_fn(problem_details)),
         )
-        .fallback(not_found_handler)
         .with_state(ApiContext {
             config: Arc::new(config),
             db,
@@ -86,6 +114,202 @@ pub async fn serve(config: Config, db: PgPool) -> anyhow::Result<()> {
         .context("error running HTTP server")
 }
 
-async fn not_found_handler(_: Uri) -> impl IntoResponse {
-    Error::NotFound
+/// Fill in the `instance` and `request_id` fields of a `application/problem+json` response
+/// with the path and `RequestId` of the request that produced it.
+///
+/// `Error::into_response` can't see the request it's responding to, so instead of threading
+/// that context through every handler, this middleware captures it up front and patches the
+/// already-serialized body on the way back out.
+async fn problem_details(request: Request<Body>, next: Next<Body>) -> Response {
+    let path = request.uri().path().to_string();
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(ToString::to_string);
+
+    let response = next.run(request).await;
+
+    let is_problem_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        == Some(PROBLEM_JSON);
+
+    if !is_problem_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+
+    let Ok(bytes) = hyper::body::to_bytes(body).await else {
+        return Response::from_parts(parts, axum::body::boxed(Body::empty()));
+    };
+
+    let Ok(mut body) = serde_json::from_slice::<serde_json::Value>(&bytes) else {
+        return Response::from_parts(parts, axum::body::boxed(Body::from(bytes)));
+    };
+
+    if let Some(object) = body.as_object_mut() {
+        object.insert("instance".to_string(), serde_json::Value::String(path));
+
+        if let Some(request_id) = request_id {
+            object.insert(
+                "request_id".to_string(),
+                serde_json::Value::String(request_id),
+            );
+        }
+    }
+
+    let bytes = serde_json::to_vec(&body).unwrap_or(bytes.to_vec());
+    parts.headers.remove(CONTENT_LENGTH);
+
+    Response::from_parts(parts, axum::body::boxed(Body::from(bytes)))
+}
+
+/// Build the [`CorsLayer`] from `config.cors_allowed_origins`.
+///
+/// An empty list (the default) allows any origin; set `CORS_ALLOWED_ORIGINS` to restrict it
+/// once the API has a known set of frontends. Credentials are never allowed cross-origin, as
+/// `allow_credentials` is left unset.
+fn cors_layer(config: &Config) -> CorsLayer {
+    let layer = CorsLayer::new()
+        .allow_methods(tower_http::cors::Any)
+        .allow_headers(tower_http::cors::Any);
+
+    if config.cors_allowed_origins.is_empty() {
+        return layer.allow_origin(tower_http::cors::Any);
+    }
+
+    let origins: Vec<HeaderValue> = config
+        .cors_allowed_origins
+        .iter()
+        .filter_map(|origin| HeaderValue::from_str(origin).ok())
+        .collect();
+
+    layer.allow_origin(AllowOrigin::list(origins))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_config(cors_allowed_origins: Vec<String>) -> Config {
+        Config {
+            database_url: "postgres://localhost/test".to_string(),
+            port: 8080,
+            address: "127.0.0.1".to_string(),
+            jwt_secret: "test-secret".to_string(),
+            jwt_max_age: 900,
+            jwt_refresh_max_age: 1_209_600,
+            cors_allowed_origins,
+            request_body_limit_bytes: 2 * 1024 * 1024,
+            compression_enabled: false,
+            upload_dir: "./uploads".into(),
+            upload_max_file_size_bytes: 5 * 1024 * 1024,
+            static_dir: "../web/out".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_allowlist_allows_any_origin() {
+        let layer = cors_layer(&test_config(Vec::new()));
+
+        let response = apply_cors(layer, "http://anything.test").await;
+        assert_eq!(
+            response.get("access-control-allow-origin").map(String::as_str),
+            Some("*")
+        );
+    }
+
+    #[tokio::test]
+    async fn allowlisted_origin_is_echoed_back() {
+        let layer = cors_layer(&test_config(vec!["http://allowed.test".to_string()]));
+
+        let response = apply_cors(layer, "http://allowed.test").await;
+        assert_eq!(
+            response.get("access-control-allow-origin").map(String::as_str),
+            Some("http://allowed.test")
+        );
+    }
+
+    #[tokio::test]
+    async fn non_allowlisted_origin_gets_no_allow_origin_header() {
+        let layer = cors_layer(&test_config(vec!["http://allowed.test".to_string()]));
+
+        let response = apply_cors(layer, "http://evil.test").await;
+        assert!(response.get("access-control-allow-origin").is_none());
+    }
+
+    #[tokio::test]
+    async fn problem_details_fills_in_instance_and_request_id() {
+        use axum::routing::get;
+        use tower::ServiceExt;
+
+        let app: Router = Router::new()
+            .route("/api/widgets/1", get(|| async { Error::NotFound.into_response() }))
+            .layer(axum::middleware::from_fn(problem_details));
+
+        let request_id = RequestId::new();
+        let request = Request::builder()
+            .uri("/api/widgets/1")
+            .extension(request_id.clone())
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+        assert_eq!(body["instance"], "/api/widgets/1");
+        assert_eq!(body["request_id"], request_id.to_string());
+    }
+
+    #[tokio::test]
+    async fn problem_details_leaves_non_problem_json_responses_untouched() {
+        use axum::routing::get;
+        use tower::ServiceExt;
+
+        let app: Router = Router::new()
+            .route("/ok", get(|| async { "plain" }))
+            .layer(axum::middleware::from_fn(problem_details));
+
+        let request = Request::builder().uri("/ok").body(Body::empty()).unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        let bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        assert_eq!(&bytes[..], b"plain");
+    }
+
+    /// Run a bare GET with an `Origin` header through `layer` and collect the response headers
+    /// as owned strings, so tests can assert on them without holding a borrow on the service.
+    async fn apply_cors(
+        layer: CorsLayer,
+        origin: &str,
+    ) -> std::collections::HashMap<String, String> {
+        use axum::http::header::ORIGIN;
+        use tower::{Service, ServiceExt};
+
+        let mut service = ServiceBuilder::new().layer(layer).service_fn(|_req: Request<Body>| async {
+            Ok::<_, std::convert::Infallible>(Response::new(Body::empty()))
+        });
+
+        let request = Request::builder()
+            .header(ORIGIN, origin)
+            .body(Body::empty())
+            .unwrap();
+
+        let response = service.ready().await.unwrap().call(request).await.unwrap();
+
+        response
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (
+                    name.as_str().to_string(),
+                    value.to_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect()
+    }
 }
@@ -9,6 +9,15 @@ pub fn router() -> Router<ApiContext> {
     Router::new().route("/", get(health))
 }
 
-async fn health() -> impl IntoResponse {
+/// Check that the service is up.
+#[utoipa::path(
+    get,
+    path = "/api",
+    tag = "health",
+    responses(
+        (status = 200, description = "The service is healthy", body = String)
+    )
+)]
+pub(crate) async fn health() -> impl IntoResponse {
     (StatusCode::OK, "ok")
 }
@@ -1,9 +1,24 @@
-mod health_check;
+pub(crate) mod health_check;
+pub(crate) mod uploads;
 
 use axum::Router;
+use utoipa::OpenApi;
+use utoipa_rapidoc::RapiDoc;
+use utoipa_redoc::{Redoc, Servable};
+use utoipa_swagger_ui::SwaggerUi;
 
+use super::openapi::ApiDoc;
 use super::ApiContext;
 
 pub fn router() -> Router<ApiContext> {
-    Router::new().merge(health_check::router())
+    Router::new()
+        .nest(
+            "/api",
+            Router::new()
+                .merge(health_check::router())
+                .merge(uploads::router()),
+        )
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
+        .merge(Redoc::with_url("/redoc", ApiDoc::openapi()))
+        .merge(RapiDoc::new("/api-docs/openapi.json").path("/rapidoc"))
 }
@@ -0,0 +1,172 @@
+use anyhow::Context;
+use axum::extract::{Multipart, State};
+use axum::response::IntoResponse;
+use axum::routing::post;
+use axum::{Json, Router};
+use image::imageops::FilterType;
+use uuid::Uuid;
+
+use crate::http::{ApiContext, Error, Result};
+
+/// Width/height (in pixels) of the box thumbnails are resized to fit, keeping the aspect ratio;
+/// images smaller than this are scaled up.
+const THUMBNAIL_DIMENSION: u32 = 256;
+
+/// Both the original and the thumbnail are re-encoded to this format before being written to
+/// disk, regardless of what was uploaded - so this, not the client-supplied content type, is
+/// what gets persisted in the `upload` table.
+const STORED_CONTENT_TYPE: &str = "image/png";
+
+pub fn router() -> Router<ApiContext> {
+    Router::new().route("/uploads", post(upload))
+}
+
+/// The stored original and thumbnail for an uploaded image.
+#[derive(Debug, serde::Serialize, utoipa::ToSchema)]
+pub struct Upload {
+    pub upload_id: i64,
+    pub original_path: String,
+    pub thumbnail_path: String,
+}
+
+/// Accept a single image via `multipart/form-data`, store the original alongside a resized
+/// thumbnail, and record both paths in the `upload` table.
+#[utoipa::path(
+    post,
+    path = "/api/uploads",
+    tag = "uploads",
+    responses(
+        (status = 200, description = "The file was stored", body = Upload),
+        (status = 400, description = "The request was not a valid multipart upload, or the file is not an image or exceeds the size limit", body = ErrorBody),
+        (status = 422, description = "The uploaded file is not a supported image", body = ErrorBody)
+    )
+)]
+pub(crate) async fn upload(
+    State(ctx): State<ApiContext>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse> {
+    let field = multipart
+        .next_field()
+        .await
+        .map_err(|_| Error::bad_request([("file", "invalid multipart request")]))?
+        .ok_or_else(|| Error::bad_request([("file", "missing a `file` field")]))?;
+
+    let filename = field
+        .file_name()
+        .map(str::to_owned)
+        .unwrap_or_else(|| "upload".to_string());
+
+    let content_type = field
+        .content_type()
+        .map(str::to_owned)
+        .or_else(|| mime_guess::from_path(&filename).first().map(|mime| mime.to_string()))
+        .ok_or(Error::UnprocessableEntity)?;
+
+    let bytes = field
+        .bytes()
+        .await
+        .map_err(|_| Error::bad_request([("file", "failed to read upload")]))?;
+
+    let image = decode_upload(&content_type, &bytes, ctx.config.upload_max_file_size_bytes)?;
+    let thumbnail = image.resize(
+        THUMBNAIL_DIMENSION,
+        THUMBNAIL_DIMENSION,
+        FilterType::Lanczos3,
+    );
+
+    tokio::fs::create_dir_all(&ctx.config.upload_dir)
+        .await
+        .context("failed to create upload directory")?;
+
+    let id = Uuid::new_v4();
+    let original_path = ctx.config.upload_dir.join(format!("{id}-original.png"));
+    let thumbnail_path = ctx.config.upload_dir.join(format!("{id}-thumbnail.png"));
+
+    image
+        .save(&original_path)
+        .map_err(|e| Error::Anyhow(e.into()))?;
+    thumbnail
+        .save(&thumbnail_path)
+        .map_err(|e| Error::Anyhow(e.into()))?;
+
+    let original_path = original_path.to_string_lossy().into_owned();
+    let thumbnail_path = thumbnail_path.to_string_lossy().into_owned();
+
+    let upload_id = sqlx::query_scalar!(
+        r#"insert into "upload" (content_type, original_path, thumbnail_path) values ($1, $2, $3) returning upload_id"#,
+        STORED_CONTENT_TYPE,
+        original_path,
+        thumbnail_path,
+    )
+    .fetch_one(&ctx.db)
+    .await?;
+
+    Ok(Json(Upload {
+        upload_id,
+        original_path,
+        thumbnail_path,
+    }))
+}
+
+/// Validate a multipart field's content type and size, then decode it as an image.
+///
+/// Pulled out of [`upload`] so the validation rules can be unit tested without a database or
+/// multipart request to drive them through.
+fn decode_upload(
+    content_type: &str,
+    bytes: &[u8],
+    max_file_size_bytes: usize,
+) -> Result<image::DynamicImage> {
+    if !content_type.starts_with("image/") {
+        return Err(Error::bad_request([(
+            "file",
+            "only image uploads are supported",
+        )]));
+    }
+
+    if bytes.len() > max_file_size_bytes {
+        return Err(Error::bad_request([(
+            "file",
+            "file exceeds the upload size limit",
+        )]));
+    }
+
+    image::load_from_memory(bytes).map_err(|_| Error::UnprocessableEntity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal valid 1x1 PNG, small enough to embed inline.
+    const PNG_1X1: &[u8] = &[
+        0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+        0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x90,
+        0x77, 0x53, 0xde, 0x00, 0x00, 0x00, 0x0c, 0x49, 0x44, 0x41, 0x54, 0x08, 0xd7, 0x63, 0xf8,
+        0xcf, 0xc0, 0x00, 0x00, 0x03, 0x01, 0x01, 0x00, 0x18, 0xdd, 0x8d, 0xb0, 0x00, 0x00, 0x00,
+        0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+    ];
+
+    #[test]
+    fn accepts_a_valid_image_within_the_size_limit() {
+        assert!(decode_upload("image/png", PNG_1X1, PNG_1X1.len()).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_non_image_content_type() {
+        let result = decode_upload("text/plain", PNG_1X1, PNG_1X1.len());
+        assert!(matches!(result, Err(Error::BadRequest { .. })));
+    }
+
+    #[test]
+    fn rejects_a_file_over_the_size_limit() {
+        let result = decode_upload("image/png", PNG_1X1, PNG_1X1.len() - 1);
+        assert!(matches!(result, Err(Error::BadRequest { .. })));
+    }
+
+    #[test]
+    fn rejects_corrupt_image_bytes() {
+        let result = decode_upload("image/png", b"not a real image", 1024);
+        assert!(matches!(result, Err(Error::UnprocessableEntity)));
+    }
+}
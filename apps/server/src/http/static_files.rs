@@ -0,0 +1,141 @@
+use std::path::{Component, Path};
+
+use axum::extract::State;
+use axum::http::{header::CONTENT_TYPE, Uri};
+use axum::response::{IntoResponse, Response};
+
+use super::{ApiContext, Error};
+
+/// Requests under this prefix never fall through to the frontend - they're either handled by
+/// a route in `routes::router()` or are a genuine `404`.
+const API_PREFIX: &str = "/api";
+
+/// Serve the compiled frontend, so the whole application can be deployed as a single binary.
+///
+/// Tries the requested path as a static asset first (resolving its content type via
+/// `mime_guess`), falls back to `index.html` so client-side routing still works, and only
+/// returns [`Error::NotFound`] for paths under [`API_PREFIX`] or when no frontend build is
+/// available at all.
+pub(crate) async fn fallback(State(ctx): State<ApiContext>, uri: Uri) -> Response {
+    if is_api_path(uri.path()) {
+        return Error::NotFound.into_response();
+    }
+
+    let path = uri.path().trim_start_matches('/');
+
+    if is_safe_asset_path(path) {
+        if let Some(asset) = read_asset(&ctx.config.static_dir, path).await {
+            return asset.into_response();
+        }
+    }
+
+    match read_asset(&ctx.config.static_dir, "index.html").await {
+        Some(asset) => asset.into_response(),
+        None => Error::NotFound.into_response(),
+    }
+}
+
+/// Whether `path` falls under [`API_PREFIX`], matched on path segments so a SPA route like
+/// `/apidocs` isn't mistaken for an API path just because it shares the same prefix string.
+fn is_api_path(path: &str) -> bool {
+    path == API_PREFIX || path.starts_with(&format!("{API_PREFIX}/"))
+}
+
+/// Reject anything but plain, relative path segments (no `..`, no `/`-rooted or
+/// drive-rooted paths) before it's joined onto `static_dir` or looked up in the embedded
+/// asset table, so a request can't escape the asset root or read arbitrary files.
+fn is_safe_asset_path(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// A static asset, ready to be turned into a response.
+struct Asset {
+    bytes: Vec<u8>,
+    content_type: String,
+}
+
+impl IntoResponse for Asset {
+    fn into_response(self) -> Response {
+        ([(CONTENT_TYPE, self.content_type)], self.bytes).into_response()
+    }
+}
+
+/// Compiled in: the frontend build is embedded in the binary at compile time, so production
+/// deployments need no extra files alongside the executable.
+#[cfg(feature = "embed-assets")]
+mod source {
+    use rust_embed::RustEmbed;
+
+    #[derive(RustEmbed)]
+    #[folder = "../web/out"]
+    pub struct Assets;
+
+    pub fn get(path: &str) -> Option<Vec<u8>> {
+        Assets::get(path).map(|file| file.data.into_owned())
+    }
+}
+
+/// Not compiled in: assets are read from `config.static_dir` on every request, so a rebuilt
+/// frontend shows up without recompiling the server.
+#[cfg(not(feature = "embed-assets"))]
+mod source {
+    use std::path::Path;
+
+    pub async fn get(static_dir: &Path, path: &str) -> Option<Vec<u8>> {
+        tokio::fs::read(static_dir.join(path)).await.ok()
+    }
+}
+
+#[cfg(feature = "embed-assets")]
+async fn read_asset(_static_dir: &std::path::Path, path: &str) -> Option<Asset> {
+    let bytes = source::get(path)?;
+    let content_type = mime_guess::from_path(path).first_or_octet_stream().to_string();
+
+    Some(Asset { bytes, content_type })
+}
+
+#[cfg(not(feature = "embed-assets"))]
+async fn read_asset(static_dir: &std::path::Path, path: &str) -> Option<Asset> {
+    let bytes = source::get(static_dir, path).await?;
+    let content_type = mime_guess::from_path(path).first_or_octet_stream().to_string();
+
+    Some(Asset { bytes, content_type })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_parent_directory_traversal() {
+        assert!(!is_safe_asset_path("../../etc/passwd"));
+        assert!(!is_safe_asset_path("foo/../../etc/passwd"));
+    }
+
+    #[test]
+    fn rejects_absolute_paths() {
+        assert!(!is_safe_asset_path("/etc/passwd"));
+    }
+
+    #[test]
+    fn rejects_an_empty_path() {
+        assert!(!is_safe_asset_path(""));
+    }
+
+    #[test]
+    fn accepts_plain_relative_paths() {
+        assert!(is_safe_asset_path("index.html"));
+        assert!(is_safe_asset_path("assets/app.js"));
+    }
+
+    #[test]
+    fn matches_the_api_prefix_on_segment_boundaries() {
+        assert!(is_api_path("/api"));
+        assert!(is_api_path("/api/uploads"));
+        assert!(!is_api_path("/apidocs"));
+        assert!(!is_api_path("/"));
+    }
+}
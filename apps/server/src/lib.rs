@@ -1,3 +1,7 @@
+/// JWT-based authentication: issuing/refreshing tokens and the [`auth::Claims`] extractor
+/// that handlers use to require an authenticated caller.
+pub mod auth;
+
 /// Defines the arguments required to start the server using [`clap`].
 ///
 /// [`clap`]: https://docs.rs/clap
@@ -1,4 +1,3 @@
-use clap::Parser;
 use dotenv::dotenv;
 use server::{config::Config, http};
 use sqlx::postgres::PgPoolOptions;
@@ -9,7 +8,7 @@ async fn main() -> anyhow::Result<()> {
 
     env_logger::init();
 
-    let config = Config::parse();
+    let config = Config::load()?;
 
     let db = PgPoolOptions::new()
         .max_connections(10)